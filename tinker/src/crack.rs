@@ -0,0 +1,278 @@
+//! Automatic recovery of a `key2`-style Vigenère key with no user input:
+//! Kasiski examination and the Friedman index of coincidence narrow down
+//! the likely key length, then per-column chi-squared frequency analysis
+//! recovers each shift.
+
+use std::error::Error;
+
+/// Standard relative frequencies of `a..=z` in English text.
+const ENGLISH_FREQ: [f64; 26] = [
+    0.08167, 0.01492, 0.02782, 0.04253, 0.12702, 0.02228, 0.02015, 0.06094, 0.06966, 0.00153,
+    0.00772, 0.04025, 0.02406, 0.06749, 0.07507, 0.01929, 0.00095, 0.05987, 0.06327, 0.09056,
+    0.02758, 0.00978, 0.02360, 0.00150, 0.01974, 0.00074,
+];
+
+/// The longest key length considered when no key is supplied.
+const MAX_KEY_LEN: usize = 20;
+
+fn gcd(a: usize, b: usize) -> usize {
+    if b == 0 {
+        a
+    } else {
+        gcd(b, a % b)
+    }
+}
+
+/// Kasiski examination: finds repeated trigrams in `letters` and returns
+/// the distances between their occurrences, used to narrow down likely
+/// key lengths.
+fn kasiski_distances(letters: &[u8]) -> Vec<usize> {
+    let mut positions: std::collections::HashMap<&[u8], Vec<usize>> = std::collections::HashMap::new();
+    if letters.len() < 3 {
+        return Vec::new();
+    }
+    for i in 0..=letters.len() - 3 {
+        positions.entry(&letters[i..i + 3]).or_default().push(i);
+    }
+
+    let mut distances = Vec::new();
+    for occurrences in positions.values() {
+        if occurrences.len() < 2 {
+            continue;
+        }
+        for pair in occurrences.windows(2) {
+            distances.push(pair[1] - pair[0]);
+        }
+    }
+    distances
+}
+
+/// Ranks candidate key lengths by how often they divide the distances
+/// found via Kasiski examination.
+fn kasiski_candidate_lengths(letters: &[u8]) -> Vec<usize> {
+    let mut votes = vec![0usize; MAX_KEY_LEN + 1];
+    for distance in kasiski_distances(letters) {
+        for len in 2..=MAX_KEY_LEN {
+            if gcd(distance, len) == len {
+                votes[len] += 1;
+            }
+        }
+    }
+    let mut candidates: Vec<usize> = (2..=MAX_KEY_LEN).collect();
+    candidates.sort_by_key(|&len| std::cmp::Reverse(votes[len]));
+    candidates
+}
+
+/// Index of coincidence of a slice of letter indices (0..=25).
+fn index_of_coincidence(letters: &[u8]) -> f64 {
+    if letters.len() < 2 {
+        return 0.0;
+    }
+    let mut counts = [0u64; 26];
+    for &l in letters {
+        counts[l as usize] += 1;
+    }
+    let n = letters.len() as f64;
+    let numerator: f64 = counts.iter().map(|&c| (c as f64) * (c as f64 - 1.0)).sum();
+    numerator / (n * (n - 1.0))
+}
+
+/// Friedman estimate: average the IoC of each of the `len` columns, for
+/// every candidate length, and return the length whose average is closest
+/// to the ~0.067 expected for English.
+fn friedman_key_length_estimate(letters: &[u8]) -> usize {
+    let mut best_len = 1;
+    let mut best_delta = f64::MAX;
+    for len in 1..=MAX_KEY_LEN.min(letters.len().max(1)) {
+        let mut columns = vec![Vec::new(); len];
+        for (i, &l) in letters.iter().enumerate() {
+            columns[i % len].push(l);
+        }
+        let avg_ioc: f64 =
+            columns.iter().map(|c| index_of_coincidence(c)).sum::<f64>() / len as f64;
+        let delta = (avg_ioc - 0.067).abs();
+        if delta < best_delta {
+            best_delta = delta;
+            best_len = len;
+        }
+    }
+    best_len
+}
+
+/// Chi-squared statistic of a column's observed letter frequencies
+/// against `ENGLISH_FREQ`, after undoing a candidate Caesar shift.
+fn chi_squared_for_shift(column: &[u8], shift: u8) -> f64 {
+    let mut counts = [0u64; 26];
+    for &c in column {
+        let plain = (c + 26 - shift) % 26;
+        counts[plain as usize] += 1;
+    }
+    let n = column.len() as f64;
+    (0..26)
+        .map(|i| {
+            let observed = counts[i] as f64 / n;
+            let expected = ENGLISH_FREQ[i];
+            (observed - expected).powi(2) / expected
+        })
+        .sum()
+}
+
+/// Finds the shift (0..=25) minimizing the chi-squared statistic for a
+/// single column, returning it alongside its score.
+fn best_shift_for_column(column: &[u8]) -> (u8, f64) {
+    (0..26u8)
+        .map(|shift| (shift, chi_squared_for_shift(column, shift)))
+        .fold((0, f64::MAX), |best, candidate| {
+            if candidate.1 < best.1 {
+                candidate
+            } else {
+                best
+            }
+        })
+}
+
+/// Recovers the key of length `len`, along with the chi-squared score of
+/// the resulting decryption averaged over its columns (lower is a better
+/// fit). Averaging rather than summing keeps the score comparable across
+/// different candidate lengths, which have different numbers of columns.
+fn recover_key_for_length(letters: &[u8], len: usize) -> (String, f64) {
+    let mut columns = vec![Vec::new(); len];
+    for (i, &l) in letters.iter().enumerate() {
+        columns[i % len].push(l);
+    }
+
+    let mut key = String::with_capacity(len);
+    let mut total_score = 0.0;
+    for column in &columns {
+        let (shift, score) = best_shift_for_column(column);
+        key.push((b'a' + shift) as char);
+        total_score += score;
+    }
+    (key, total_score / len as f64)
+}
+
+/// Chi-squared statistic of `letters`' overall frequency against
+/// `ENGLISH_FREQ`.
+fn unigram_chi_squared(letters: &[u8]) -> f64 {
+    let mut counts = [0u64; 26];
+    for &l in letters {
+        counts[l as usize] += 1;
+    }
+    let n = letters.len() as f64;
+    (0..26)
+        .map(|i| {
+            let observed = counts[i] as f64 / n;
+            let expected = ENGLISH_FREQ[i];
+            (observed - expected).powi(2) / expected
+        })
+        .sum()
+}
+
+/// Above this unigram chi-squared score, `text` is considered too far
+/// from English letter frequencies to be a correct decryption.
+const CHI_SQUARED_THRESHOLD: f64 = 0.5;
+
+/// Reports whether `text` looks like valid English plaintext, using a
+/// unigram chi-squared statistic against `ENGLISH_FREQ`. Unlike the index
+/// of coincidence (which only measures how "peaky" the distribution is,
+/// and is identical for every Caesar shift of the same text), chi-squared
+/// against a fixed reference distribution is sensitive to the shift
+/// itself, so it can reject a candidate key that is merely a uniform
+/// shift away from the correct one.
+pub fn looks_like_english(text: &str) -> bool {
+    if text.is_empty() || !text.chars().all(|c| c.is_ascii_graphic() || c.is_ascii_whitespace()) {
+        return false;
+    }
+    let letters: Vec<u8> = text
+        .chars()
+        .filter(|c| c.is_ascii_alphabetic())
+        .map(|c| c.to_ascii_lowercase() as u8 - b'a')
+        .collect();
+    if letters.is_empty() {
+        return false;
+    }
+    unigram_chi_squared(&letters) < CHI_SQUARED_THRESHOLD
+}
+
+/// Fraction by which the per-column chi-squared score of a length
+/// neither Kasiski nor Friedman favored is penalized, so the two
+/// heuristics actually influence the final pick instead of only being
+/// logged.
+const UNSUPPORTED_LENGTH_PENALTY: f64 = 1.05;
+
+/// Recovers a `key2`-style alphabetic key from `file` with no user input.
+///
+/// Kasiski examination and the Friedman index of coincidence are used to
+/// shortlist likely key lengths; every length from 1 to [`MAX_KEY_LEN`]
+/// is still tried (so a wrong heuristic guess can't sink the result),
+/// but lengths outside that shortlist are penalized when comparing
+/// chi-squared scores, and the length with the best adjusted score wins.
+pub fn crack(file: String) -> Result<String, Box<dyn Error>> {
+    let ciphertext = std::fs::read_to_string(file)?;
+    let letters: Vec<u8> = ciphertext
+        .chars()
+        .filter(|c| c.is_ascii_alphabetic())
+        .map(|c| c.to_ascii_lowercase() as u8 - b'a')
+        .collect();
+
+    let kasiski = kasiski_candidate_lengths(&letters);
+    let friedman = friedman_key_length_estimate(&letters);
+    let shortlist: Vec<usize> = kasiski.iter().take(3).copied().chain([friedman]).collect();
+    println!(
+        "Kasiski candidate lengths: {:?}, Friedman estimate: {}",
+        kasiski.iter().take(5).collect::<Vec<_>>(),
+        friedman
+    );
+
+    let mut best_key = String::new();
+    let mut best_score = f64::MAX;
+    for len in 1..=MAX_KEY_LEN {
+        let (key, score) = recover_key_for_length(&letters, len);
+        let adjusted_score = if shortlist.contains(&len) {
+            score
+        } else {
+            score * UNSUPPORTED_LENGTH_PENALTY
+        };
+        if adjusted_score < best_score {
+            best_score = adjusted_score;
+            best_key = key;
+        }
+    }
+
+    Ok(best_key)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tinker::encryptdecrypt;
+
+    /// Encrypts a long, repeated passage of English text with a known
+    /// Vigenère key and checks that `crack` recovers that key with no
+    /// input other than the ciphertext file.
+    #[test]
+    fn recovers_known_vigenere_key() {
+        let plaintext = "the quick brown fox jumps over the lazy dog while the \
+            sun sets slowly over the quiet hill and the wind blows gently \
+            through the trees near the old stone bridge where travelers \
+            once rested after long journeys across the wide green valley"
+            .repeat(8);
+
+        let key = b"rust";
+        let mut ciphertext = Vec::new();
+        let mut reader = plaintext.as_bytes();
+        encryptdecrypt(&mut reader, &mut ciphertext, key, false).unwrap();
+
+        let path = std::env::temp_dir().join(format!(
+            "fe2o3-crack-test-{}-{}.txt",
+            std::process::id(),
+            "recovers_known_vigenere_key"
+        ));
+        std::fs::write(&path, &ciphertext).unwrap();
+
+        let recovered = crack(path.to_string_lossy().to_string()).unwrap();
+        std::fs::remove_file(&path).ok();
+
+        assert_eq!(recovered, "rust");
+    }
+}
@@ -0,0 +1,45 @@
+//! Minimal `Read`/`Write` traits for streaming bytes in fixed-size chunks,
+//! so callers never have to buffer an entire payload into memory.
+
+use std::io;
+
+/// A source of bytes that can be pulled in chunks.
+pub trait Read {
+    /// Fill `buf` with up to `buf.len()` bytes, returning how many were
+    /// read. Returns `Ok(0)` once the source is exhausted.
+    fn read_chunk(&mut self, buf: &mut [u8]) -> io::Result<usize>;
+
+    /// Fills `buf` completely, looping until enough bytes have been read.
+    fn read_exact_chunk(&mut self, mut buf: &mut [u8]) -> io::Result<()> {
+        while !buf.is_empty() {
+            match self.read_chunk(buf)? {
+                0 => {
+                    return Err(io::Error::new(
+                        io::ErrorKind::UnexpectedEof,
+                        "unexpected end of stream",
+                    ))
+                }
+                n => buf = &mut buf[n..],
+            }
+        }
+        Ok(())
+    }
+}
+
+/// A sink that bytes can be pushed to in chunks.
+pub trait Write {
+    /// Write all of `buf` to the sink.
+    fn write_chunk(&mut self, buf: &[u8]) -> io::Result<()>;
+}
+
+impl<R: std::io::Read> Read for R {
+    fn read_chunk(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.read(buf)
+    }
+}
+
+impl<W: std::io::Write> Write for W {
+    fn write_chunk(&mut self, buf: &[u8]) -> io::Result<()> {
+        self.write_all(buf)
+    }
+}
@@ -1,107 +1,285 @@
 mod tinker;
 mod crypto;
+mod io;
+mod crack;
+mod manifest;
 use std::error::Error;
+use std::path::{Path, PathBuf};
 
-use clap::Parser;
-use crypto::{decrypt1_rs, decrypt2_rs, encrypt1_rs, encrypt2_rs};
+use clap::{Parser, Subcommand};
+use rand::Rng;
 
-use crate::tinker::*;
+use crate::crack::{crack, looks_like_english};
+use crate::manifest::{to_hex, FileEntry, Manifest};
+use crypto::{decrypt1_rs, decrypt2_rs, decrypt3_rs, encrypt1_rs, encrypt2_rs, encrypt3_rs};
 
-fn parse_key(s: &str) -> Result<Option<String>, String> {
-    if s.len() != 5 {
-        Err("key must be 32 characters".to_string())
+fn parse_key(s: &str) -> Result<String, String> {
+    if s.is_empty() || !s.chars().all(|c| c.is_ascii_alphabetic()) {
+        Err("key must be a non-empty alphabetic string".to_string())
     } else {
-        Ok(Some(s.to_string()))
+        Ok(s.to_string())
     }
 }
 
-fn parse_file(file_name: &str) -> Result<String, String> {
-    let file = std::fs::read_to_string(file_name);
-    match file {
-        Ok(file) => Ok(file),
-        Err(_) => Err("File not found".to_string()),
-    }
-}
-
-/// Simple program to greet a person
+/// A small toy cryptography CLI
 #[derive(Parser, Debug)]
 #[command(author = "Microsoft", version = "1", about, long_about = None)]
-struct Args {
-    /// Decrypt mode
-    #[arg(
-        short,
-        long,
-        conflicts_with = "encrypt1",
-    )]
-    decrypt1: bool,
-
-    /// Encrypt mode
-    #[arg(
-        short,
-        long,
-        conflicts_with = "decrypt1",
-    )]
-    encrypt1: bool,
-    /// Decrypt mode
-    #[arg(
-        short,
-        long,
-        conflicts_with = "encrypt2",
-    )]
-    decrypt2: bool,
-
-    /// Encrypt mode
-    #[arg(
-        short,
-        long,
-        conflicts_with = "decrypt2",
-    )]
-    encrypt2: bool,
-
-    /// Encryption file
-    #[arg(short, long, required = true, value_parser = parse_file)]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Encrypt a file
+    Encrypt(CipherArgs),
+    /// Decrypt a file
+    Decrypt(CipherArgs),
+    /// Recover a Vigenère-style key with no user input
+    Crack {
+        /// Ciphertext file
+        file: String,
+    },
+    /// Generate a random alphabetic key for the Vigenère mode
+    Keygen {
+        /// Length of the key to generate
+        #[arg(short, long, default_value_t = 5)]
+        length: usize,
+    },
+    /// Check whether a candidate key produces English-looking plaintext
+    Verify {
+        /// Ciphertext file
+        file: String,
+        /// Candidate Vigenère key
+        #[arg(value_parser = parse_key)]
+        key: String,
+    },
+}
+
+#[derive(clap::Args, Debug)]
+struct CipherArgs {
+    /// File (or, with `--recursive`, directory) to encrypt/decrypt
     file: String,
 
-    /// Output file
-    #[arg(short, long, default_value=None)]
+    /// Output file (or directory, with `--recursive`)
+    #[arg(short, long)]
     outfile: Option<String>,
 
-    #[arg(short, long, required = false, default_value = None, value_parser = parse_key)]
-    key1: Option<i32>,
-    #[arg(short, long, required = false, default_value = None, value_parser = parse_key)]
-    key2: Option<String>,
+    /// Treat `file` as a directory and mirror it, encrypting each file
+    /// independently (requires the `chacha20` mode)
+    #[arg(short, long)]
+    recursive: bool,
+
+    #[command(subcommand)]
+    mode: Mode,
+}
+
+#[derive(Subcommand, Debug)]
+enum Mode {
+    /// Caesar shift cipher
+    Caesar {
+        /// Integer shift
+        #[arg(short, long)]
+        key: i32,
+    },
+    /// Vigenère cipher
+    Vigenere {
+        /// Alphabetic key; any length matches a `Keygen`-produced key
+        #[arg(short, long, value_parser = parse_key)]
+        key: String,
+    },
+    /// ChaCha20 stream cipher
+    Chacha20 {
+        /// Passphrase the key is derived from
+        #[arg(short, long)]
+        key: String,
+    },
+}
+
+fn run_cipher(args: CipherArgs, dec: bool) -> Result<(), Box<dyn Error>> {
+    let outfile = args.outfile.unwrap_or("out.txt".to_string());
+    let mut reader = std::fs::File::open(&args.file)?;
+    let mut writer = std::fs::File::create(&outfile)?;
+
+    match args.mode {
+        Mode::Caesar { key } if dec => decrypt1_rs(&mut reader, &mut writer, key)?,
+        Mode::Caesar { key } => encrypt1_rs(&mut reader, &mut writer, key)?,
+        Mode::Vigenere { key } if dec => decrypt2_rs(&mut reader, &mut writer, key)?,
+        Mode::Vigenere { key } => encrypt2_rs(&mut reader, &mut writer, key)?,
+        Mode::Chacha20 { key } if dec => decrypt3_rs(&mut reader, &mut writer, key)?,
+        Mode::Chacha20 { key } => {
+            encrypt3_rs(&mut reader, &mut writer, key)?;
+        }
+    }
+    Ok(())
+}
+
+fn generate_key(length: usize) -> String {
+    let mut rng = rand::thread_rng();
+    (0..length).map(|_| (b'a' + rng.gen_range(0..26)) as char).collect()
+}
+
+/// Walks `dir`, recursing into subdirectories, and records every regular
+/// file in `files` and every directory (including empty ones) in `dirs`
+/// so the whole tree shape can be mirrored, not just the files in it.
+fn walk_dir(dir: &Path, files: &mut Vec<PathBuf>, dirs: &mut Vec<PathBuf>) -> Result<(), Box<dyn Error>> {
+    for entry in std::fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            dirs.push(path.clone());
+            walk_dir(&path, files, dirs)?;
+        } else {
+            files.push(path);
+        }
+    }
+    Ok(())
+}
+
+/// Reserved name of the manifest file written at the root of the output
+/// directory; a source file mapping to this name would otherwise be
+/// silently clobbered by the manifest write.
+const MANIFEST_FILE_NAME: &str = "manifest.json";
+
+/// Encrypts every file under `args.file` into a mirrored directory tree
+/// under `args.outfile`, recording per-file nonces and hashes (and every
+/// directory, so empty ones are preserved too) in a top-level
+/// `manifest.json`.
+fn encrypt_dir(args: CipherArgs, key: String) -> Result<(), Box<dyn Error>> {
+    let input_dir = Path::new(&args.file);
+    let output_dir = PathBuf::from(args.outfile.unwrap_or("out".to_string()));
+    std::fs::create_dir_all(&output_dir)?;
+
+    let mut paths = Vec::new();
+    let mut dir_paths = Vec::new();
+    walk_dir(input_dir, &mut paths, &mut dir_paths)?;
+
+    let mut manifest = Manifest::default();
+    for dir in dir_paths {
+        let rel = dir.strip_prefix(input_dir)?.to_string_lossy().to_string();
+        std::fs::create_dir_all(output_dir.join(&rel))?;
+        manifest.dirs.push(rel);
+    }
+
+    for path in paths {
+        let rel = path.strip_prefix(input_dir)?.to_string_lossy().to_string();
+        if rel == MANIFEST_FILE_NAME {
+            return Err(format!(
+                "cannot encrypt a source file named `{}` at the root of the tree: it collides with the directory manifest",
+                MANIFEST_FILE_NAME
+            )
+            .into());
+        }
+        let out_path = output_dir.join(&rel);
+        if let Some(parent) = out_path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+
+        let size = std::fs::metadata(&path)?.len();
+        let sha256 = sha256::digest(std::fs::read(&path)?.as_slice());
+
+        let mut reader = std::fs::File::open(&path)?;
+        let mut writer = std::fs::File::create(&out_path)?;
+        let nonce = encrypt3_rs(&mut reader, &mut writer, key.clone())?;
+
+        manifest.files.push(FileEntry {
+            path: rel,
+            size,
+            nonce: to_hex(&nonce),
+            sha256,
+        });
+    }
+
+    let manifest_file = std::fs::File::create(output_dir.join(MANIFEST_FILE_NAME))?;
+    manifest.write_to(manifest_file)?;
+    Ok(())
+}
+
+/// Reconstructs the original tree from a directory produced by
+/// [`encrypt_dir`], driven by its `manifest.json`. Every decrypted file
+/// is re-hashed and checked against the manifest's recorded SHA-256, so
+/// a corrupted or tampered file is caught instead of silently passed
+/// through.
+fn decrypt_dir(args: CipherArgs, key: String) -> Result<(), Box<dyn Error>> {
+    let input_dir = Path::new(&args.file);
+    let output_dir = PathBuf::from(args.outfile.unwrap_or("out".to_string()));
+    std::fs::create_dir_all(&output_dir)?;
+
+    let manifest_file = std::fs::File::open(input_dir.join(MANIFEST_FILE_NAME))?;
+    let manifest = Manifest::read_from(manifest_file)?;
+
+    for dir in &manifest.dirs {
+        std::fs::create_dir_all(output_dir.join(dir))?;
+    }
+
+    for entry in &manifest.files {
+        let in_path = input_dir.join(&entry.path);
+        let out_path = output_dir.join(&entry.path);
+        if let Some(parent) = out_path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+
+        let mut reader = std::fs::File::open(&in_path)?;
+        let mut writer = std::fs::File::create(&out_path)?;
+        decrypt3_rs(&mut reader, &mut writer, key.clone())?;
+
+        let actual_sha256 = sha256::digest(std::fs::read(&out_path)?.as_slice());
+        if actual_sha256 != entry.sha256 {
+            return Err(format!(
+                "integrity check failed for {}: expected sha256 {}, got {}",
+                entry.path, entry.sha256, actual_sha256
+            )
+            .into());
+        }
+    }
+    Ok(())
 }
 
 fn main() -> Result<(), Box<dyn Error>> {
-    let cli = Args::parse();
-
-    // Open Encrypted File
-    if cli.decrypt1 {
-        let encrypted_file = cli.file;
-        println!("Decrypting...");
-        let out = decrypt1_rs(encrypted_file, cli.key1.unwrap());
-        let outfile = cli.outfile.unwrap_or("out.txt".to_string());
-        std::fs::write(outfile, out)?;
-    } else if cli.encrypt1 {
-        println!("Encrypting...");
-        let out = encrypt1_rs(cli.file, cli.key1.unwrap());
-        let outfile = cli.outfile.unwrap_or("out.txt".to_string());
-        std::fs::write(outfile, out)?;
-    } else if cli.decrypt2 {
-        let encrypted_file = cli.file;
-        println!("Decrypting...");
-        let out = decrypt2_rs(encrypted_file, cli.key2.unwrap());
-        let outfile = cli.outfile.unwrap_or("out.txt".to_string());
-        std::fs::write(outfile, out)?;
-    } else if cli.encrypt2 {
-        println!("Encrypting...");
-        let out = encrypt2_rs(cli.file, cli.key2.unwrap());
-        let outfile = cli.outfile.unwrap_or("out.txt".to_string());
-        std::fs::write(outfile, out)?;
-    } else {
-        println!("Calling cracking fn...");
-        let outkey = crack(cli.file)?;
-        println!("Cracked key: {}", outkey)
+    let cli = Cli::parse();
+
+    match cli.command {
+        Command::Encrypt(args) if args.recursive => {
+            println!("Encrypting directory...");
+            let key = match &args.mode {
+                Mode::Chacha20 { key } => key.clone(),
+                _ => return Err("--recursive requires the chacha20 mode".into()),
+            };
+            encrypt_dir(args, key)?;
+        }
+        Command::Decrypt(args) if args.recursive => {
+            println!("Decrypting directory...");
+            let key = match &args.mode {
+                Mode::Chacha20 { key } => key.clone(),
+                _ => return Err("--recursive requires the chacha20 mode".into()),
+            };
+            decrypt_dir(args, key)?;
+        }
+        Command::Encrypt(args) => {
+            println!("Encrypting...");
+            run_cipher(args, false)?;
+        }
+        Command::Decrypt(args) => {
+            println!("Decrypting...");
+            run_cipher(args, true)?;
+        }
+        Command::Crack { file } => {
+            println!("Calling cracking fn...");
+            let key = crack(file)?;
+            println!("Cracked key: {}", key);
+        }
+        Command::Keygen { length } => {
+            println!("{}", generate_key(length));
+        }
+        Command::Verify { file, key } => {
+            let mut reader = std::fs::File::open(&file)?;
+            let mut plaintext = Vec::new();
+            decrypt2_rs(&mut reader, &mut plaintext, key)?;
+            if looks_like_english(&String::from_utf8_lossy(&plaintext)) {
+                println!("key looks valid");
+            } else {
+                println!("key looks invalid");
+            }
+        }
     }
     Ok(())
 }
@@ -110,38 +288,176 @@ fn main() -> Result<(), Box<dyn Error>> {
 mod tests {
     use super::*;
 
-    // TODO: Run crack functions and get the key
     const key1: i32 = 10;
     const key2: &str = "rust";
     const key3: &str = "rustiscool";
+    const SAMPLE_PLAINTEXT: &[u8] = b"the quick brown fox jumps over the lazy dog, 42 times!";
 
     #[test]
     fn test_encrypted__1() {
-        let file = "input1/encrypted1.txt";
-        let result = decrypt1_rs(file.to_string(), key1.into());
-        
-        // Hash the result
-        let result = sha256::digest(result.as_bytes());
-        assert_eq!(result, "16b1a5e0e6db690416b4cc00e878ede9a2a61ef3ed3a848a4dd933fe199539b4");
+        let mut ciphertext = Vec::new();
+        let mut reader = SAMPLE_PLAINTEXT;
+        encrypt1_rs(&mut reader, &mut ciphertext, key1).unwrap();
+
+        let mut out = Vec::new();
+        let mut reader = ciphertext.as_slice();
+        decrypt1_rs(&mut reader, &mut out, key1).unwrap();
+
+        assert_eq!(out, SAMPLE_PLAINTEXT);
     }
 
+    // Also covers the index % keychars.len() fix: key2 is shorter than the
+    // hardcoded `% 5` this used to be.
     #[test]
     fn test_encrypted__2() {
-        let file = "input1/encrypted2.txt";
-        let result = decrypt2_rs(file.to_string(), key2.into());
-        
-        // Hash the result
-        let result = sha256::digest(result.as_bytes());
-        assert_eq!(result, "16b1a5e0e6db690416b4cc00e878ede9a2a61ef3ed3a848a4dd933fe199539b4");
+        let mut ciphertext = Vec::new();
+        let mut reader = SAMPLE_PLAINTEXT;
+        encrypt2_rs(&mut reader, &mut ciphertext, key2.into()).unwrap();
+
+        let mut out = Vec::new();
+        let mut reader = ciphertext.as_slice();
+        decrypt2_rs(&mut reader, &mut out, key2.into()).unwrap();
+
+        assert_eq!(out, SAMPLE_PLAINTEXT);
     }
 
+    // Also covers the index % keychars.len() fix: key3 is longer than the
+    // hardcoded `% 5` this used to be.
     #[test]
     fn test_encrypted__3() {
-        let file = "input1/encrypted3.txt";
-        let result = decrypt2_rs(file.to_string(), key3.into());
-        
-        // Hash the result
-        let result = sha256::digest(result.as_bytes());
-        assert_eq!(result, "16b1a5e0e6db690416b4cc00e878ede9a2a61ef3ed3a848a4dd933fe199539b4");
+        let mut ciphertext = Vec::new();
+        let mut reader = SAMPLE_PLAINTEXT;
+        encrypt2_rs(&mut reader, &mut ciphertext, key3.into()).unwrap();
+        // The 6th letter ('i', byte 6) is shifted by key3[5] = 's', giving
+        // 'a'; wrapping at 5 would have used key3[0] = 'r' and given 'z'.
+        assert_eq!(ciphertext[6], b'a');
+
+        let mut out = Vec::new();
+        let mut reader = ciphertext.as_slice();
+        decrypt2_rs(&mut reader, &mut out, key3.into()).unwrap();
+
+        assert_eq!(out, SAMPLE_PLAINTEXT);
+    }
+
+    #[test]
+    fn test_chacha20_roundtrip() {
+        // A payload larger than CHUNK_SIZE plus some non-ASCII bytes, to
+        // exercise both chunk-boundary handling and binary-safety.
+        let mut plaintext: Vec<u8> = (0..5000u32).map(|i| (i % 256) as u8).collect();
+        plaintext.extend_from_slice(&[0xff, 0xfe, 0x00, 0x80, 0xc3, 0xa9]);
+
+        let key = "correct horse battery staple".to_string();
+
+        let mut ciphertext = Vec::new();
+        let mut reader = plaintext.as_slice();
+        encrypt3_rs(&mut reader, &mut ciphertext, key.clone()).unwrap();
+
+        let mut roundtripped = Vec::new();
+        let mut reader = ciphertext.as_slice();
+        decrypt3_rs(&mut reader, &mut roundtripped, key).unwrap();
+
+        assert_eq!(roundtripped, plaintext);
     }
-}
\ No newline at end of file
+
+    fn cipher_args(file: String, outfile: String, key: &str) -> CipherArgs {
+        CipherArgs {
+            file,
+            outfile: Some(outfile),
+            recursive: true,
+            mode: Mode::Chacha20 { key: key.to_string() },
+        }
+    }
+
+    #[test]
+    fn test_recursive_roundtrip_and_tamper_detection() {
+        let base = std::env::temp_dir().join(format!(
+            "fe2o3-dir-test-{}-roundtrip",
+            std::process::id()
+        ));
+        std::fs::remove_dir_all(&base).ok();
+        let input_dir = base.join("input");
+        let output_dir = base.join("encrypted");
+        let restored_dir = base.join("restored");
+
+        std::fs::create_dir_all(input_dir.join("nested")).unwrap();
+        std::fs::create_dir_all(input_dir.join("empty")).unwrap();
+        std::fs::write(input_dir.join("a.txt"), b"hello world").unwrap();
+        std::fs::write(input_dir.join("nested/b.bin"), [0u8, 159, 255, 1, 2, 3]).unwrap();
+
+        let key = "correct horse battery staple";
+
+        encrypt_dir(
+            cipher_args(
+                input_dir.to_string_lossy().to_string(),
+                output_dir.to_string_lossy().to_string(),
+                key,
+            ),
+            key.to_string(),
+        )
+        .unwrap();
+        assert!(output_dir.join("empty").is_dir());
+
+        decrypt_dir(
+            cipher_args(
+                output_dir.to_string_lossy().to_string(),
+                restored_dir.to_string_lossy().to_string(),
+                key,
+            ),
+            key.to_string(),
+        )
+        .unwrap();
+
+        assert_eq!(std::fs::read(restored_dir.join("a.txt")).unwrap(), b"hello world");
+        assert_eq!(
+            std::fs::read(restored_dir.join("nested/b.bin")).unwrap(),
+            vec![0u8, 159, 255, 1, 2, 3]
+        );
+        assert!(restored_dir.join("empty").is_dir());
+
+        // Tamper with a ciphertext file and confirm the integrity check fires.
+        let mut tampered = std::fs::read(output_dir.join("a.txt")).unwrap();
+        let last = tampered.len() - 1;
+        tampered[last] ^= 0xff;
+        std::fs::write(output_dir.join("a.txt"), &tampered).unwrap();
+
+        let tampered_restore_dir = base.join("restored-tampered");
+        let result = decrypt_dir(
+            cipher_args(
+                output_dir.to_string_lossy().to_string(),
+                tampered_restore_dir.to_string_lossy().to_string(),
+                key,
+            ),
+            key.to_string(),
+        );
+        assert!(result.is_err());
+
+        std::fs::remove_dir_all(&base).ok();
+    }
+
+    #[test]
+    fn test_recursive_rejects_manifest_name_collision() {
+        let base = std::env::temp_dir().join(format!(
+            "fe2o3-dir-test-{}-collision",
+            std::process::id()
+        ));
+        std::fs::remove_dir_all(&base).ok();
+        let input_dir = base.join("input");
+        let output_dir = base.join("encrypted");
+
+        std::fs::create_dir_all(&input_dir).unwrap();
+        std::fs::write(input_dir.join(MANIFEST_FILE_NAME), b"not a manifest").unwrap();
+
+        let key = "correct horse battery staple";
+        let result = encrypt_dir(
+            cipher_args(
+                input_dir.to_string_lossy().to_string(),
+                output_dir.to_string_lossy().to_string(),
+                key,
+            ),
+            key.to_string(),
+        );
+        assert!(result.is_err());
+
+        std::fs::remove_dir_all(&base).ok();
+    }
+}
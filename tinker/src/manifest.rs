@@ -0,0 +1,47 @@
+//! Manifest for the `--recursive` directory mode: records enough
+//! metadata about each encrypted file to mirror the original tree back
+//! on decryption.
+
+use std::error::Error;
+use std::io;
+
+use serde::{Deserialize, Serialize};
+
+/// Metadata for a single encrypted file.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct FileEntry {
+    /// Path relative to the root of the encrypted directory.
+    pub path: String,
+    /// Size, in bytes, of the original plaintext file.
+    pub size: u64,
+    /// Hex-encoded ChaCha20 nonce used for this file.
+    pub nonce: String,
+    /// SHA-256 hash of the original plaintext, for integrity checking.
+    pub sha256: String,
+}
+
+/// Top-level manifest describing an encrypted directory mirror.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct Manifest {
+    pub files: Vec<FileEntry>,
+    /// Paths (relative to the root) of every directory in the original
+    /// tree, including ones with no files, so empty directories are
+    /// mirrored too.
+    pub dirs: Vec<String>,
+}
+
+impl Manifest {
+    pub fn write_to(&self, writer: impl io::Write) -> Result<(), Box<dyn Error>> {
+        serde_json::to_writer_pretty(writer, self)?;
+        Ok(())
+    }
+
+    pub fn read_from(reader: impl io::Read) -> Result<Self, Box<dyn Error>> {
+        Ok(serde_json::from_reader(reader)?)
+    }
+}
+
+/// Encodes `bytes` as a lowercase hex string.
+pub fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
@@ -0,0 +1,158 @@
+use std::error::Error;
+
+use chacha20::cipher::{KeyIvInit, StreamCipher};
+use chacha20::ChaCha20;
+use rand::RngCore;
+use sha2::{Digest, Sha256};
+
+use crate::io::{Read, Write};
+
+/// Bytes processed per chunk. Keeps memory use constant regardless of
+/// input size.
+const CHUNK_SIZE: usize = 4096;
+
+/// Length, in bytes, of the ChaCha20 nonce header written before the
+/// ciphertext.
+pub const NONCE_LEN: usize = 12;
+
+fn encdec(c: char, keychar: char, dec: bool) -> char {
+    if dec {
+        let mut cphr = c as i32 - 'a' as i32;
+        let keychar_i32 = keychar as i32 - 'a' as i32;
+        cphr = if cphr - keychar_i32 < 0 {
+            26 + (cphr - keychar_i32)
+        } else {
+            cphr - keychar_i32
+        };
+        cphr += 'a' as i32;
+        cphr as u8 as char
+    } else {
+        let c_i32 = c as i32;
+        let keychar_i32 = keychar as i32 - 'a' as i32;
+        let cphr = (c_i32 - 'a' as i32 + keychar_i32) % 26 + 'a' as i32;
+        cphr as u8 as char
+    }
+}
+
+/// Caesar-shifts a byte stream by a constant amount. Used for the
+/// `encrypt1`/`decrypt1` modes.
+pub fn shift_stream<R: Read, W: Write>(
+    reader: &mut R,
+    writer: &mut W,
+    shift: i32,
+    dec: bool,
+) -> Result<(), Box<dyn Error>> {
+    let keychar = ((shift.rem_euclid(26)) as u8 + b'a') as char;
+    let mut buf = [0u8; CHUNK_SIZE];
+    let mut out = Vec::with_capacity(CHUNK_SIZE);
+    loop {
+        let n = reader.read_chunk(&mut buf)?;
+        if n == 0 {
+            break;
+        }
+        out.clear();
+        for &byte in &buf[..n] {
+            if byte.is_ascii_alphabetic() {
+                out.push(encdec(byte.to_ascii_lowercase() as char, keychar, dec) as u8);
+            } else {
+                out.push(byte);
+            }
+        }
+        writer.write_chunk(&out)?;
+    }
+    Ok(())
+}
+
+/// Keyed stream cipher used for the `encrypt2`/`decrypt2` modes. The
+/// keystream position (`index % keychars.len()`) is tracked across calls
+/// to `read_chunk` so the cipher is correct no matter how the input is
+/// chunked. Only ASCII letters are ciphered; every other byte (including
+/// non-ASCII bytes) passes through unchanged so binary files round-trip.
+pub fn encryptdecrypt<R: Read, W: Write>(
+    reader: &mut R,
+    writer: &mut W,
+    keychars: &[u8],
+    dec: bool,
+) -> Result<(), Box<dyn Error>> {
+    let mut index = 0usize;
+    let mut buf = [0u8; CHUNK_SIZE];
+    let mut out = Vec::with_capacity(CHUNK_SIZE);
+    loop {
+        let n = reader.read_chunk(&mut buf)?;
+        if n == 0 {
+            break;
+        }
+        out.clear();
+        for &byte in &buf[..n] {
+            if byte.is_ascii_alphabetic() {
+                let lower = byte.to_ascii_lowercase() as char;
+                let keyindex = index % keychars.len();
+                out.push(encdec(lower, keychars[keyindex] as char, dec) as u8);
+                index += 1;
+            } else {
+                out.push(byte);
+            }
+        }
+        writer.write_chunk(&out)?;
+    }
+    Ok(())
+}
+
+/// Derives a 256-bit ChaCha20 key from a user-supplied passphrase.
+fn derive_key(passphrase: &str) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    hasher.update(passphrase.as_bytes());
+    hasher.finalize().into()
+}
+
+/// Encrypts `reader` into `writer` with ChaCha20. A fresh random 96-bit
+/// nonce is generated and written as a fixed-length header so that
+/// `decrypt3` can recover it without the caller re-supplying it; the
+/// nonce is also returned so callers (e.g. the manifest builder for
+/// `--recursive`) can record it alongside the file.
+pub fn encrypt3<R: Read, W: Write>(
+    reader: &mut R,
+    writer: &mut W,
+    passphrase: &str,
+) -> Result<[u8; NONCE_LEN], Box<dyn Error>> {
+    let key = derive_key(passphrase);
+    let mut nonce = [0u8; NONCE_LEN];
+    rand::thread_rng().fill_bytes(&mut nonce);
+    writer.write_chunk(&nonce)?;
+
+    let mut cipher = ChaCha20::new(&key.into(), &nonce.into());
+    let mut buf = [0u8; CHUNK_SIZE];
+    loop {
+        let n = reader.read_chunk(&mut buf)?;
+        if n == 0 {
+            break;
+        }
+        cipher.apply_keystream(&mut buf[..n]);
+        writer.write_chunk(&buf[..n])?;
+    }
+    Ok(nonce)
+}
+
+/// Decrypts a stream produced by [`encrypt3`], reading the nonce header
+/// back off `reader` before decrypting the remainder.
+pub fn decrypt3<R: Read, W: Write>(
+    reader: &mut R,
+    writer: &mut W,
+    passphrase: &str,
+) -> Result<(), Box<dyn Error>> {
+    let key = derive_key(passphrase);
+    let mut nonce = [0u8; NONCE_LEN];
+    reader.read_exact_chunk(&mut nonce)?;
+
+    let mut cipher = ChaCha20::new(&key.into(), &nonce.into());
+    let mut buf = [0u8; CHUNK_SIZE];
+    loop {
+        let n = reader.read_chunk(&mut buf)?;
+        if n == 0 {
+            break;
+        }
+        cipher.apply_keystream(&mut buf[..n]);
+        writer.write_chunk(&buf[..n])?;
+    }
+    Ok(())
+}